@@ -0,0 +1,307 @@
+//! Shared logic for opening a slorpit archive and reading individual files
+//! out of it, used by the extractor as well as tools that browse an
+//! archive without doing a full extraction pass (the FUSE mount, the
+//! interactive shell).
+
+use anyhow::{Context, Result, anyhow};
+use lopdf::{Document, Object, ObjectId};
+use std::path::Path;
+
+use crate::compress::{decompress_flate, decompress_with_dictionary};
+use crate::crypto;
+use crate::{
+    ArchiveCatalog, CATALOG_KEY, CompressionAlgorithm, ENCRYPTION_ALGORITHM_KEY,
+    ENCRYPTION_SALT_KEY, EntryType, FileEntry, STREAM_COMPRESSION_KEY, STREAM_NONCE_KEY,
+};
+
+/// A loaded archive, ready to have individual files read out of it on
+/// demand.
+pub struct OpenArchive {
+    pub doc: Document,
+    pub catalog: ArchiveCatalog,
+    file_streams: Vec<ObjectId>,
+    decoder_dict: Option<zstd::dict::DecoderDictionary<'static>>,
+    decrypt_key: Option<[u8; crypto::KEY_LEN]>,
+}
+
+impl OpenArchive {
+    /// Open an archive, deriving a decryption key from `password` if the
+    /// catalog was written with `--encrypt`. `password` is ignored for
+    /// unencrypted archives.
+    pub fn open(path: &Path, password: Option<&str>) -> Result<Self> {
+        let doc = Document::load(path)
+            .with_context(|| format!("Failed to load PDF from {}", path.display()))?;
+
+        let catalog_id = find_catalog_id(&doc)?;
+        let (catalog, decrypt_key) = extract_catalog(&doc, catalog_id, password)?;
+        let file_streams = find_file_streams(&doc)?;
+
+        let decoder_dict = match catalog.compression {
+            CompressionAlgorithm::ZstdDict => {
+                Some(load_dictionary(&doc, &catalog, decrypt_key.as_ref())?)
+            }
+            CompressionAlgorithm::Flate => None,
+        };
+
+        Ok(Self {
+            doc,
+            catalog,
+            file_streams,
+            decoder_dict,
+            decrypt_key,
+        })
+    }
+
+    /// The derived decryption key, if the archive was opened with a
+    /// password matching an encrypted catalog. Exposed so tools like
+    /// `rebuild`, which need to re-encrypt catalog data for their own
+    /// output, can reuse the key instead of re-running the (deliberately
+    /// slow) Argon2 derivation.
+    pub fn decrypt_key(&self) -> Option<&[u8; crypto::KEY_LEN]> {
+        self.decrypt_key.as_ref()
+    }
+
+    /// Resolve the `EmbeddedFile` stream for catalog index `idx`, falling
+    /// back to sorted-order matching for legacy `EntryType::File` archives
+    /// without an explicit `FileEntry::stream` reference.
+    ///
+    /// Only `EntryType::File` entries ever have a stream: the positional
+    /// fallback indexes into `file_streams`, which only contains actual
+    /// `EmbeddedFile` objects, so applying it to a non-file entry's catalog
+    /// index would resolve to an unrelated file's stream.
+    pub fn resolved_stream(&self, idx: usize) -> Option<ObjectId> {
+        let file_entry = self.catalog.files.get(idx)?;
+        file_entry.stream.or_else(|| {
+            if file_entry.entry_type == EntryType::File {
+                self.file_streams.get(idx).copied()
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Decompress and return the content of the file at catalog index `idx`.
+    pub fn read_file(&self, idx: usize) -> Result<Vec<u8>> {
+        let file_entry = self
+            .catalog
+            .files
+            .get(idx)
+            .context("No such file index in catalog")?;
+        let stream_id = self
+            .resolved_stream(idx)
+            .with_context(|| format!("Missing stream for {}", file_entry.path))?;
+
+        extract_file_content(
+            &self.doc,
+            stream_id,
+            self.decoder_dict.as_ref(),
+            self.decrypt_key.as_ref(),
+            file_entry,
+        )
+    }
+}
+
+/// Pull the raw bytes out of a PDF string object, as used for binary blobs
+/// like salts and nonces that don't round-trip cleanly through `as_name()`.
+fn object_bytes(obj: &Object) -> Option<&[u8]> {
+    match obj {
+        Object::String(bytes, _) => Some(bytes),
+        _ => None,
+    }
+}
+
+pub fn find_catalog_id(doc: &Document) -> Result<ObjectId> {
+    let trailer = &doc.trailer;
+    let root_obj = trailer.get(b"Root").context("No Root in PDF trailer")?;
+    let root_id = root_obj.as_reference().context("Root is not a reference")?;
+
+    let root = doc
+        .get_object(root_id)
+        .context("Failed to get root object")?
+        .as_dict()
+        .context("Invalid Root catalog")?;
+
+    let catalog_obj = root
+        .get(CATALOG_KEY.as_bytes())
+        .context("No Slorpit catalog found in PDF")?;
+    let catalog_id = catalog_obj
+        .as_reference()
+        .context("Catalog is not a reference")?;
+
+    Ok(catalog_id)
+}
+
+/// Load and parse the catalog, decrypting it first if it was written with
+/// `--encrypt`. Returns the derived key alongside the catalog so callers can
+/// reuse it to decrypt the dictionary and file streams without re-running
+/// the (deliberately slow) Argon2 derivation.
+pub fn extract_catalog(
+    doc: &Document,
+    catalog_id: ObjectId,
+    password: Option<&str>,
+) -> Result<(ArchiveCatalog, Option<[u8; crypto::KEY_LEN]>)> {
+    let catalog_obj = doc
+        .get_object(catalog_id)
+        .map_err(|e| anyhow!("Catalog object not found: {}", e))?;
+
+    let stream = catalog_obj
+        .as_stream()
+        .map_err(|e| anyhow!("Catalog is not a stream: {}", e))?;
+
+    let key = match stream.dict.get(ENCRYPTION_ALGORITHM_KEY.as_bytes()).ok() {
+        Some(algo_obj) => {
+            let algo = algo_obj
+                .as_name()
+                .map_err(|e| anyhow!("Invalid encryption algorithm entry: {}", e))?;
+            if algo != crypto::ALGORITHM.as_bytes() {
+                anyhow::bail!(
+                    "Unsupported encryption algorithm: {}",
+                    String::from_utf8_lossy(algo)
+                );
+            }
+            let password = password.context("Archive is encrypted; a password is required")?;
+            let salt = stream
+                .dict
+                .get(ENCRYPTION_SALT_KEY.as_bytes())
+                .ok()
+                .and_then(object_bytes)
+                .context("Encrypted catalog missing salt")?;
+            Some(crypto::derive_key(password, salt)?)
+        }
+        None => None,
+    };
+
+    let raw_content = if stream.dict.get(b"Filter").is_ok() {
+        stream.decompressed_content()?
+    } else {
+        stream.content.clone()
+    };
+
+    let content = match &key {
+        Some(key) => {
+            let nonce = stream
+                .dict
+                .get(STREAM_NONCE_KEY.as_bytes())
+                .ok()
+                .and_then(object_bytes)
+                .context("Encrypted catalog missing nonce")?;
+            crypto::decrypt(key, nonce, &raw_content)?
+        }
+        None => raw_content,
+    };
+
+    let catalog: ArchiveCatalog =
+        serde_json::from_slice(&content).with_context(|| "Failed to parse catalog JSON")?;
+
+    Ok((catalog, key))
+}
+
+/// Collect all `EmbeddedFile` streams sorted by object id.
+///
+/// Only used as a fallback for legacy archives whose `FileEntry` records
+/// predate the explicit `stream` reference field.
+pub fn find_file_streams(doc: &Document) -> Result<Vec<ObjectId>> {
+    let mut file_streams = Vec::new();
+
+    for (object_id, object) in &doc.objects {
+        if let Ok(stream) = object.as_stream() {
+            if let Ok(dict) = stream.dict.get(b"Type") {
+                if let Ok(type_name) = dict.as_name() {
+                    if type_name == b"EmbeddedFile" {
+                        file_streams.push(*object_id);
+                    }
+                }
+            }
+        }
+    }
+
+    file_streams.sort_by_key(|(id, generation)| (*id, *generation));
+
+    Ok(file_streams)
+}
+
+pub fn load_dictionary(
+    doc: &Document,
+    catalog: &ArchiveCatalog,
+    decrypt_key: Option<&[u8; crypto::KEY_LEN]>,
+) -> Result<zstd::dict::DecoderDictionary<'static>> {
+    let dict_id = catalog
+        .dictionary
+        .context("Archive claims zstd-dict compression but has no dictionary reference")?;
+
+    let dict_obj = doc
+        .get_object(dict_id)
+        .map_err(|e| anyhow!("Dictionary object not found: {}", e))?;
+    let stream = dict_obj
+        .as_stream()
+        .map_err(|e| anyhow!("Dictionary is not a stream: {}", e))?;
+
+    let content = match decrypt_key {
+        Some(key) => {
+            let nonce = stream
+                .dict
+                .get(STREAM_NONCE_KEY.as_bytes())
+                .ok()
+                .and_then(object_bytes)
+                .context("Encrypted dictionary missing nonce")?;
+            crypto::decrypt(key, nonce, &stream.content)?
+        }
+        None => stream.content.clone(),
+    };
+
+    Ok(zstd::dict::DecoderDictionary::copy(&content))
+}
+
+pub fn extract_file_content(
+    doc: &Document,
+    object_id: ObjectId,
+    decoder_dict: Option<&zstd::dict::DecoderDictionary>,
+    decrypt_key: Option<&[u8; crypto::KEY_LEN]>,
+    file_entry: &FileEntry,
+) -> Result<Vec<u8>> {
+    let object = doc
+        .get_object(object_id)
+        .map_err(|e| anyhow!("Object not found: {:?}: {}", object_id, e))?;
+
+    let stream = object
+        .as_stream()
+        .map_err(|e| anyhow!("Object is not a stream: {:?}: {}", object_id, e))?;
+
+    let algorithm = stream
+        .dict
+        .get(STREAM_COMPRESSION_KEY.as_bytes())
+        .ok()
+        .and_then(|o| o.as_name().ok())
+        .unwrap_or(b"flate");
+
+    let compressed = match decrypt_key {
+        Some(key) => {
+            let nonce = stream
+                .dict
+                .get(STREAM_NONCE_KEY.as_bytes())
+                .ok()
+                .and_then(object_bytes)
+                .with_context(|| format!("Encrypted stream {:?} missing nonce", object_id))?;
+            crypto::decrypt(key, nonce, &stream.content)
+                .with_context(|| format!("Failed to decrypt stream: {:?}", object_id))?
+        }
+        None => stream.content.clone(),
+    };
+
+    if algorithm == b"zstd-dict" {
+        let dict = decoder_dict
+            .context("Stream is zstd-dict compressed but no archive dictionary was loaded")?;
+        decompress_with_dictionary(&compressed, dict, file_entry.size as usize)
+            .with_context(|| format!("Failed to decompress stream: {:?}", object_id))
+    } else if decrypt_key.is_some() {
+        // Encrypted streams hold ciphertext in place of the usual
+        // Filter-compressed bytes, so lopdf's automatic decoding can't run;
+        // decompress what we just decrypted ourselves instead.
+        decompress_flate(&compressed)
+            .with_context(|| format!("Failed to decompress stream: {:?}", object_id))
+    } else {
+        stream
+            .decompressed_content()
+            .with_context(|| format!("Failed to decompress stream: {:?}", object_id))
+    }
+}
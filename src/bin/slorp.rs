@@ -1,64 +1,220 @@
 use anyhow::{Context, Result};
 use lopdf::{Document, Object, Stream, dictionary};
-use slorpit::{ArchiveCatalog, CATALOG_KEY, FileEntry};
+use slorpit::compress::{compress_flate, compress_with_dictionary, train_dictionary};
+use slorpit::crypto;
+use slorpit::{
+    ArchiveCatalog, CATALOG_KEY, CompressionAlgorithm, DICTIONARY_KEY, ENCRYPTION_ALGORITHM_KEY,
+    ENCRYPTION_KDF_KEY, ENCRYPTION_SALT_KEY, EncryptionMetadata, EntryType, FileEntry,
+    STREAM_NONCE_KEY,
+};
+use std::collections::HashMap;
 use std::fs;
+use std::os::unix::fs::MetadataExt;
 use std::path::Path;
 use walkdir::WalkDir;
 
-fn main() -> Result<()> {
-    let args: Vec<String> = std::env::args().collect();
+/// A filesystem entry read off disk but not yet embedded, so all regular
+/// file contents can be sampled before we decide whether to train a
+/// dictionary.
+struct PendingEntry {
+    relative_path: String,
+    entry_type: EntryType,
+    content: Option<Vec<u8>>,
+    size: u64,
+    modified: Option<u64>,
+    mode: Option<u32>,
+    uid: Option<u32>,
+    gid: Option<u32>,
+    link_target: Option<String>,
+}
+
+struct Args {
+    output_path: String,
+    input_paths: Vec<String>,
+    encrypt: bool,
+    password: Option<String>,
+}
+
+fn parse_args() -> Args {
+    let raw: Vec<String> = std::env::args().collect();
+    let mut positional = Vec::new();
+    let mut encrypt = false;
+    let mut password = None;
+
+    let mut iter = raw.into_iter().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--encrypt" => encrypt = true,
+            "--password" => {
+                password = iter.next();
+            }
+            other => positional.push(other.to_string()),
+        }
+    }
 
-    if args.len() < 3 {
-        eprintln!("Usage: {} <output.pdf> <files...>", args[0]);
+    if positional.len() < 2 {
+        eprintln!(
+            "Usage: slorp <output.pdf> <files...> [--encrypt --password PASSWORD]"
+        );
         std::process::exit(1);
     }
 
-    let output_path = &args[1];
-    let input_paths: Vec<&str> = args[2..].iter().map(|s| s.as_str()).collect();
+    Args {
+        output_path: positional[0].clone(),
+        input_paths: positional[1..].to_vec(),
+        encrypt,
+        password,
+    }
+}
+
+fn main() -> Result<()> {
+    let args = parse_args();
+
+    if args.encrypt && args.password.is_none() {
+        anyhow::bail!("--encrypt requires --password <password>");
+    }
 
-    println!("Creating PDF archive: {}", output_path);
+    println!("Creating PDF archive: {}", args.output_path);
 
     let mut doc = Document::with_version("1.5");
     let pages_id = doc.new_object_id();
     let mut catalog = ArchiveCatalog::new();
 
-    let mut file_objects = Vec::new();
+    let encrypt_key = if args.encrypt {
+        let salt = crypto::random_salt();
+        let key = crypto::derive_key(args.password.as_deref().unwrap(), &salt)?;
+        catalog.encryption = Some(EncryptionMetadata {
+            algorithm: crypto::ALGORITHM.to_string(),
+            kdf: crypto::KDF.to_string(),
+            salt: salt.to_vec(),
+        });
+        println!("Encrypting archive contents with a password-derived key");
+        Some(key)
+    } else {
+        None
+    };
+
+    let mut pending = Vec::new();
+    let mut seen_inodes: HashMap<(u64, u64), String> = HashMap::new();
 
-    for input_path in &input_paths {
+    for input_path in &args.input_paths {
         let path = Path::new(input_path);
 
-        if path.is_file() {
-            process_file(&mut doc, &path, &path, &mut catalog, &mut file_objects)?;
-        } else if path.is_dir() {
+        let Ok(metadata) = path.symlink_metadata() else {
+            eprintln!("Warning: {} does not exist, skipping", input_path);
+            continue;
+        };
+
+        // Use symlink_metadata (not is_dir, which follows symlinks) so a
+        // symlink-to-directory passed directly on the command line is
+        // archived as a symlink, not dereferenced into a real directory
+        // tree - matching how entries found during the walk are recorded.
+        if metadata.is_dir() {
+            // min_depth(1) skips the root entry itself (depth 0): its
+            // relative_path would come out empty and fall back to just the
+            // directory's own basename, landing it as a bogus sibling of
+            // its own children instead of their ancestor.
             for entry in WalkDir::new(path)
+                .min_depth(1)
                 .into_iter()
                 .filter_map(|e| e.ok())
-                .filter(|e| e.file_type().is_file())
             {
-                process_file(
-                    &mut doc,
-                    entry.path(),
-                    path,
-                    &mut catalog,
-                    &mut file_objects,
-                )?;
+                pending.push(read_entry(entry.path(), path, &mut seen_inodes)?);
             }
         } else {
-            eprintln!(
-                "Warning: {} is neither a file nor directory, skipping",
-                input_path
+            pending.push(read_entry(path, path, &mut seen_inodes)?);
+        }
+    }
+
+    let samples: Vec<Vec<u8>> = pending
+        .iter()
+        .filter_map(|e| e.content.clone())
+        .filter(|c| !c.is_empty())
+        .collect();
+    let dictionary = train_dictionary(&samples);
+
+    let encoder_dict = if let Some(dict_bytes) = &dictionary {
+        println!(
+            "Trained zstd dictionary ({} bytes) from {} samples",
+            dict_bytes.len(),
+            samples.len()
+        );
+        catalog.compression = CompressionAlgorithm::ZstdDict;
+
+        let (stored_bytes, dict_nonce) = match &encrypt_key {
+            Some(key) => {
+                let nonce = crypto::random_nonce();
+                let ciphertext = crypto::encrypt(key, &nonce, dict_bytes)?;
+                (ciphertext, Some(nonce))
+            }
+            None => (dict_bytes.clone(), None),
+        };
+
+        let mut dict_stream_dict = dictionary! {
+            "Type" => "Metadata",
+            "Subtype" => "SlorpitDictionary",
+        };
+        if let Some(nonce) = &dict_nonce {
+            dict_stream_dict.set(
+                STREAM_NONCE_KEY,
+                Object::String(nonce.to_vec(), lopdf::StringFormat::Literal),
             );
         }
+
+        let dict_id = doc.add_object(Stream::new(dict_stream_dict, stored_bytes));
+        catalog.dictionary = Some(dict_id);
+        Some(zstd::dict::EncoderDictionary::copy(dict_bytes, 19))
+    } else {
+        println!("Not enough similar samples to train a dictionary, using plain flate");
+        None
+    };
+
+    for entry in pending {
+        embed_entry(
+            &mut doc,
+            entry,
+            encoder_dict.as_ref(),
+            &mut catalog,
+            encrypt_key.as_ref(),
+        )?;
     }
 
     let catalog_json = serde_json::to_string(&catalog)?;
-    let catalog_stream = Stream::new(
-        dictionary! {
-            "Type" => "Metadata",
-            "Subtype" => "SlorpitArchive",
-        },
-        catalog_json.into_bytes(),
-    );
+    let (catalog_bytes, catalog_nonce) = match &encrypt_key {
+        Some(key) => {
+            let nonce = crypto::random_nonce();
+            let ciphertext = crypto::encrypt(key, &nonce, catalog_json.as_bytes())?;
+            (ciphertext, Some(nonce))
+        }
+        None => (catalog_json.into_bytes(), None),
+    };
+
+    let mut catalog_stream_dict = dictionary! {
+        "Type" => "Metadata",
+        "Subtype" => "SlorpitArchive",
+    };
+    if let Some(meta) = &catalog.encryption {
+        catalog_stream_dict.set(
+            ENCRYPTION_ALGORITHM_KEY,
+            Object::Name(meta.algorithm.as_bytes().to_vec()),
+        );
+        catalog_stream_dict.set(
+            ENCRYPTION_KDF_KEY,
+            Object::Name(meta.kdf.as_bytes().to_vec()),
+        );
+        catalog_stream_dict.set(
+            ENCRYPTION_SALT_KEY,
+            Object::String(meta.salt.clone(), lopdf::StringFormat::Literal),
+        );
+    }
+    if let Some(nonce) = &catalog_nonce {
+        catalog_stream_dict.set(
+            STREAM_NONCE_KEY,
+            Object::String(nonce.to_vec(), lopdf::StringFormat::Literal),
+        );
+    }
+
+    let catalog_stream = Stream::new(catalog_stream_dict, catalog_bytes);
     let catalog_id = doc.add_object(catalog_stream);
 
     let font_id = doc.add_object(dictionary! {
@@ -67,7 +223,7 @@ fn main() -> Result<()> {
         "BaseFont" => "Courier",
     });
 
-    let page_content = create_file_listing_content(&catalog)?;
+    let page_content = slorpit::page::create_file_listing_content(&catalog)?;
     let content_stream = Stream::new(dictionary! {}, page_content.as_bytes().to_vec());
     let content_id = doc.add_object(content_stream);
 
@@ -90,11 +246,14 @@ fn main() -> Result<()> {
     };
     doc.objects.insert(pages_id, Object::Dictionary(pages));
 
-    let catalog_dict = dictionary! {
+    let mut catalog_dict = dictionary! {
         "Type" => "Catalog",
         "Pages" => pages_id,
         CATALOG_KEY => catalog_id,
     };
+    if let Some(dict_id) = catalog.dictionary {
+        catalog_dict.set(DICTIONARY_KEY, Object::Reference(dict_id));
+    }
     let catalog_obj_id = doc.add_object(catalog_dict);
     doc.trailer.set("Root", catalog_obj_id);
 
@@ -106,163 +265,202 @@ fn main() -> Result<()> {
         .max_objects_per_stream(200)
         .build();
 
-    let mut file = fs::File::create(output_path)
-        .with_context(|| format!("Failed to create output file {}", output_path))?;
+    let mut file = fs::File::create(&args.output_path)
+        .with_context(|| format!("Failed to create output file {}", args.output_path))?;
     doc.save_with_options(&mut file, save_options)
-        .with_context(|| format!("Failed to save PDF to {}", output_path))?;
+        .with_context(|| format!("Failed to save PDF to {}", args.output_path))?;
 
     println!(
         "Successfully archived {} files to {}",
         catalog.files.len(),
-        output_path
+        args.output_path
     );
 
     Ok(())
 }
 
-fn process_file(
-    doc: &mut Document,
-    file_path: &Path,
+fn read_entry(
+    entry_path: &Path,
     base_path: &Path,
-    catalog: &mut ArchiveCatalog,
-    _file_objects: &mut Vec<(u32, u16)>,
-) -> Result<()> {
-    let mut relative_path = file_path
+    seen_inodes: &mut HashMap<(u64, u64), String>,
+) -> Result<PendingEntry> {
+    let mut relative_path = entry_path
         .strip_prefix(base_path)
-        .unwrap_or(file_path)
+        .unwrap_or(entry_path)
         .to_string_lossy()
         .to_string();
 
     if relative_path.is_empty() {
-        relative_path = file_path
+        relative_path = entry_path
             .file_name()
             .map(|n| n.to_string_lossy().to_string())
             .unwrap_or_else(|| "unknown".to_string());
     }
 
-    println!("  Adding: {}", relative_path);
-
-    let content =
-        fs::read(file_path).with_context(|| format!("Failed to read {}", file_path.display()))?;
-
-    let metadata = fs::metadata(file_path)?;
+    let metadata = fs::symlink_metadata(entry_path)
+        .with_context(|| format!("Failed to stat {}", entry_path.display()))?;
     let modified = metadata
         .modified()
         .ok()
         .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
         .map(|d| d.as_secs());
+    let mode = Some(metadata.mode() & 0o7777);
+    let uid = Some(metadata.uid());
+    let gid = Some(metadata.gid());
+
+    let file_type = metadata.file_type();
+
+    if file_type.is_dir() {
+        return Ok(PendingEntry {
+            relative_path,
+            entry_type: EntryType::Directory,
+            content: None,
+            size: 0,
+            modified,
+            mode,
+            uid,
+            gid,
+            link_target: None,
+        });
+    }
 
-    let compressed = compress_data(&content)?;
+    if file_type.is_symlink() {
+        let target = fs::read_link(entry_path)
+            .with_context(|| format!("Failed to read symlink {}", entry_path.display()))?;
+        return Ok(PendingEntry {
+            relative_path,
+            entry_type: EntryType::Symlink,
+            content: None,
+            size: 0,
+            modified,
+            mode,
+            uid,
+            gid,
+            link_target: Some(target.to_string_lossy().to_string()),
+        });
+    }
+
+    if metadata.nlink() > 1 {
+        let inode_key = (metadata.dev(), metadata.ino());
+        if let Some(first_path) = seen_inodes.get(&inode_key) {
+            return Ok(PendingEntry {
+                relative_path,
+                entry_type: EntryType::Hardlink,
+                content: None,
+                size: metadata.size(),
+                modified,
+                mode,
+                uid,
+                gid,
+                link_target: Some(first_path.clone()),
+            });
+        }
+        seen_inodes.insert(inode_key, relative_path.clone());
+    }
+
+    let content = fs::read(entry_path)
+        .with_context(|| format!("Failed to read {}", entry_path.display()))?;
+    let size = content.len() as u64;
+
+    Ok(PendingEntry {
+        relative_path,
+        entry_type: EntryType::File,
+        content: Some(content),
+        size,
+        modified,
+        mode,
+        uid,
+        gid,
+        link_target: None,
+    })
+}
+
+fn embed_entry(
+    doc: &mut Document,
+    entry: PendingEntry,
+    encoder_dict: Option<&zstd::dict::EncoderDictionary>,
+    catalog: &mut ArchiveCatalog,
+    encrypt_key: Option<&[u8; crypto::KEY_LEN]>,
+) -> Result<()> {
+    println!("  Adding: {}", entry.relative_path);
+
+    let content = match &entry.content {
+        Some(content) => content,
+        None => {
+            catalog.files.push(FileEntry {
+                path: entry.relative_path,
+                size: entry.size,
+                modified: entry.modified,
+                stream: None,
+                entry_type: entry.entry_type,
+                mode: entry.mode,
+                uid: entry.uid,
+                gid: entry.gid,
+                link_target: entry.link_target,
+            });
+            return Ok(());
+        }
+    };
+
+    let (compressed, algorithm) = match encoder_dict {
+        Some(dict) => (compress_with_dictionary(content, dict)?, "zstd-dict"),
+        None => (compress_flate(content)?, "flate"),
+    };
+
+    let (stream_bytes, nonce) = match encrypt_key {
+        Some(key) => {
+            let nonce = crypto::random_nonce();
+            let ciphertext = crypto::encrypt(key, &nonce, &compressed)?;
+            (ciphertext, Some(nonce))
+        }
+        None => (compressed, None),
+    };
 
     let mut stream_dict = dictionary! {
         "Type" => "EmbeddedFile",
-        "Length" => compressed.len() as i64,
-        "Filter" => "FlateDecode",
+        "Length" => stream_bytes.len() as i64,
     };
 
+    if encoder_dict.is_none() && nonce.is_none() {
+        stream_dict.set("Filter", Object::Name(b"FlateDecode".to_vec()));
+    }
+    stream_dict.set(
+        slorpit::STREAM_COMPRESSION_KEY,
+        Object::Name(algorithm.as_bytes().to_vec()),
+    );
+    if let Some(dict_id) = catalog.dictionary {
+        stream_dict.set(DICTIONARY_KEY, Object::Reference(dict_id));
+    }
+    if let Some(nonce) = &nonce {
+        stream_dict.set(
+            STREAM_NONCE_KEY,
+            Object::String(nonce.to_vec(), lopdf::StringFormat::Literal),
+        );
+    }
+
     stream_dict.set(
         "FileName",
         Object::String(
-            relative_path.as_bytes().to_vec(),
+            entry.relative_path.as_bytes().to_vec(),
             lopdf::StringFormat::Literal,
         ),
     );
 
-    let stream = Stream::new(stream_dict, compressed);
+    let stream = Stream::new(stream_dict, stream_bytes);
 
-    let _object_id = doc.add_object(stream);
+    let object_id = doc.add_object(stream);
 
     catalog.files.push(FileEntry {
-        path: relative_path,
-        size: content.len() as u64,
-        modified,
+        path: entry.relative_path,
+        size: entry.size,
+        modified: entry.modified,
+        stream: Some(object_id),
+        entry_type: entry.entry_type,
+        mode: entry.mode,
+        uid: entry.uid,
+        gid: entry.gid,
+        link_target: entry.link_target,
     });
 
     Ok(())
 }
-
-fn create_file_listing_content(catalog: &ArchiveCatalog) -> Result<String> {
-    let mut content = String::new();
-
-    content.push_str("BT\n");
-    content.push_str("/F1 12 Tf\n");
-    content.push_str("50 750 Td\n");
-    content.push_str("(SLORPIT PDF Archive) Tj\n");
-    content.push_str("0 -20 Td\n");
-    content.push_str("/F1 10 Tf\n");
-
-    let header = format!("(Archive contains {} files)", catalog.files.len());
-    content.push_str(&header);
-    content.push_str(" Tj\n");
-    content.push_str("0 -25 Td\n");
-
-    content.push_str("/F1 9 Tf\n");
-    content.push_str("(Filename) Tj\n");
-    content.push_str("300 0 Td\n");
-    content.push_str("(Size) Tj\n");
-    content.push_str("100 0 Td\n");
-    content.push_str("(Modified) Tj\n");
-    content.push_str("-400 -15 Td\n");
-
-    for file in &catalog.files {
-        let filename = escape_pdf_string(&file.path);
-        content.push_str(&format!("({}) Tj\n", filename));
-        content.push_str("300 0 Td\n");
-
-        let size_str = format_size(file.size);
-        content.push_str(&format!("({}) Tj\n", size_str));
-        content.push_str("100 0 Td\n");
-
-        let modified_str = if let Some(ts) = file.modified {
-            format_timestamp(ts)
-        } else {
-            "N/A".to_string()
-        };
-        content.push_str(&format!("({}) Tj\n", modified_str));
-
-        content.push_str("-400 -12 Td\n");
-    }
-
-    content.push_str("ET\n");
-
-    Ok(content)
-}
-
-fn escape_pdf_string(s: &str) -> String {
-    s.replace('\\', "\\\\")
-        .replace('(', "\\(")
-        .replace(')', "\\)")
-        .chars()
-        .filter(|c| c.is_ascii() && !c.is_control())
-        .collect()
-}
-
-fn format_size(bytes: u64) -> String {
-    if bytes < 1024 {
-        format!("{} B", bytes)
-    } else if bytes < 1024 * 1024 {
-        format!("{:.1} KB", bytes as f64 / 1024.0)
-    } else {
-        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
-    }
-}
-
-fn format_timestamp(ts: u64) -> String {
-    use chrono::DateTime;
-
-    if let Some(datetime) = DateTime::from_timestamp(ts as i64, 0) {
-        datetime.format("%Y-%m-%d %H:%M").to_string()
-    } else {
-        "N/A".to_string()
-    }
-}
-
-fn compress_data(data: &[u8]) -> Result<Vec<u8>> {
-    use flate2::Compression;
-    use flate2::write::ZlibEncoder;
-    use std::io::Write;
-
-    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
-    encoder.write_all(data)?;
-    Ok(encoder.finish()?)
-}
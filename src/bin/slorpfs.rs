@@ -0,0 +1,345 @@
+//! Mounts a slorpit PDF archive read-only via FUSE, so individual files can
+//! be browsed and copied out without a full extraction pass.
+
+use anyhow::{Context, Result, anyhow};
+use fuser::{
+    FileAttr, FileType, Filesystem, MountOption, ReplyAttr, ReplyData, ReplyDirectory,
+    ReplyEntry, Request,
+};
+use slorpit::archive::OpenArchive;
+use slorpit::tree::{Node, Tree};
+use slorpit::EntryType;
+use std::ffi::OsStr;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const TTL: Duration = Duration::from_secs(1);
+const ROOT_INO: u64 = 1;
+
+/// One entry in the inode table, built once at mount time from the
+/// reconstructed directory tree.
+enum Inode {
+    Dir { children: Vec<(String, u64)> },
+    File { index: usize },
+    /// A symlink/hardlink/device/fifo/socket entry: it has catalog metadata
+    /// (shown via `getattr`/`readdir`) but no `EmbeddedFile` stream to back
+    /// a `read()`.
+    Other { index: usize },
+}
+
+struct SlorpitFs {
+    archive: OpenArchive,
+    inodes: Vec<Inode>,
+    attrs: Vec<FileAttr>,
+    /// The most recently decompressed file, keyed by inode. `read()` is
+    /// called once per chunk (typically ~128 KiB), so without this a single
+    /// large-file read through the mount would re-decompress the whole
+    /// stream from scratch on every call.
+    read_cache: Option<(u64, Vec<u8>)>,
+}
+
+impl SlorpitFs {
+    fn new(archive: OpenArchive) -> Self {
+        let tree = Tree::build(&archive.catalog.files);
+        let mut inodes = Vec::new();
+        let mut attrs = Vec::new();
+
+        // Inode 0 is unused by FUSE; push a placeholder so `inodes[ino]`
+        // lines up with FUSE's 1-based inode numbers.
+        inodes.push(Inode::Dir {
+            children: Vec::new(),
+        });
+        attrs.push(dir_attr(0, 0));
+
+        build_inodes(&tree.root, &mut inodes, &mut attrs, &archive);
+
+        Self {
+            archive,
+            inodes,
+            attrs,
+            read_cache: None,
+        }
+    }
+
+    fn attr(&self, ino: u64) -> Option<FileAttr> {
+        self.attrs.get(ino as usize).copied()
+    }
+
+    /// Decompress file `index` (inode `ino`), reusing the cached buffer if
+    /// the last `read()` was against the same inode.
+    fn cached_content(&mut self, ino: u64, index: usize) -> Result<&[u8]> {
+        if self.read_cache.as_ref().map(|(cached_ino, _)| *cached_ino) != Some(ino) {
+            let content = self.archive.read_file(index)?;
+            self.read_cache = Some((ino, content));
+        }
+        Ok(&self.read_cache.as_ref().unwrap().1)
+    }
+}
+
+fn build_inodes(root: &Node, inodes: &mut Vec<Inode>, attrs: &mut Vec<FileAttr>, archive: &OpenArchive) -> u64 {
+    // Reserve inode 1 for the root up front, since FUSE assumes it exists
+    // before any lookup happens.
+    inodes.push(Inode::Dir {
+        children: Vec::new(),
+    });
+    attrs.push(dir_attr(ROOT_INO, 0));
+    let root_ino = ROOT_INO;
+
+    if let Node::Dir { children, .. } = root {
+        let entries = build_children(children, inodes, attrs, archive);
+        inodes[root_ino as usize] = Inode::Dir { children: entries };
+    }
+
+    root_ino
+}
+
+fn build_children(
+    children: &std::collections::BTreeMap<String, Node>,
+    inodes: &mut Vec<Inode>,
+    attrs: &mut Vec<FileAttr>,
+    archive: &OpenArchive,
+) -> Vec<(String, u64)> {
+    let mut entries = Vec::new();
+
+    for (name, node) in children {
+        match node {
+            Node::File(idx) => {
+                let file = &archive.catalog.files[*idx];
+                let ino = inodes.len() as u64;
+                inodes.push(Inode::File { index: *idx });
+                attrs.push(file_attr(ino, file.size, file.modified, FileType::RegularFile));
+                entries.push((name.clone(), ino));
+            }
+            Node::Other(idx) => {
+                let file = &archive.catalog.files[*idx];
+                let ino = inodes.len() as u64;
+                inodes.push(Inode::Other { index: *idx });
+                attrs.push(file_attr(ino, file.size, file.modified, entry_file_type(file.entry_type)));
+                entries.push((name.clone(), ino));
+            }
+            Node::Dir { children: grandchildren, .. } => {
+                let ino = inodes.len() as u64;
+                // Reserve the slot before recursing so nested inode numbers
+                // come after it.
+                inodes.push(Inode::Dir {
+                    children: Vec::new(),
+                });
+                attrs.push(dir_attr(ino, 0));
+                let sub_entries = build_children(grandchildren, inodes, attrs, archive);
+                inodes[ino as usize] = Inode::Dir {
+                    children: sub_entries,
+                };
+                entries.push((name.clone(), ino));
+            }
+        }
+    }
+
+    entries
+}
+
+fn dir_attr(ino: u64, nlink: u32) -> FileAttr {
+    let now = UNIX_EPOCH;
+    FileAttr {
+        ino,
+        size: 0,
+        blocks: 0,
+        atime: now,
+        mtime: now,
+        ctime: now,
+        crtime: now,
+        kind: FileType::Directory,
+        perm: 0o555,
+        nlink: nlink + 2,
+        uid: 0,
+        gid: 0,
+        rdev: 0,
+        blksize: 512,
+        flags: 0,
+    }
+}
+
+fn file_attr(ino: u64, size: u64, modified: Option<u64>, kind: FileType) -> FileAttr {
+    let mtime = modified
+        .map(|secs| UNIX_EPOCH + Duration::from_secs(secs))
+        .unwrap_or(UNIX_EPOCH);
+    FileAttr {
+        ino,
+        size,
+        blocks: size.div_ceil(512),
+        atime: mtime,
+        mtime,
+        ctime: mtime,
+        crtime: mtime,
+        kind,
+        perm: 0o444,
+        nlink: 1,
+        uid: 0,
+        gid: 0,
+        rdev: 0,
+        blksize: 512,
+        flags: 0,
+    }
+}
+
+/// Map a non-file catalog entry's type to the `FileType` shown in
+/// `readdir`/`getattr`. There's no stream to back a `read()` for any of
+/// these, so this is metadata-only.
+fn entry_file_type(entry_type: EntryType) -> FileType {
+    match entry_type {
+        EntryType::Symlink => FileType::Symlink,
+        EntryType::Fifo => FileType::NamedPipe,
+        EntryType::Socket => FileType::Socket,
+        EntryType::CharDevice => FileType::CharDevice,
+        EntryType::BlockDevice => FileType::BlockDevice,
+        // Hardlinks have no distinct FUSE file type; approximate as a
+        // regular file (reads still correctly fail, since Inode::Other has
+        // no stream to serve).
+        EntryType::Hardlink => FileType::RegularFile,
+        EntryType::File | EntryType::Directory => FileType::RegularFile,
+    }
+}
+
+impl Filesystem for SlorpitFs {
+    fn lookup(&mut self, _req: &Request, parent: u64, name: &OsStr, reply: ReplyEntry) {
+        let Some(Inode::Dir { children }) = self.inodes.get(parent as usize) else {
+            reply.error(libc::ENOTDIR);
+            return;
+        };
+
+        let name = name.to_string_lossy();
+        match children.iter().find(|(n, _)| n == name.as_ref()) {
+            Some((_, ino)) => reply.entry(&TTL, &self.attr(*ino).unwrap(), 0),
+            None => reply.error(libc::ENOENT),
+        }
+    }
+
+    fn getattr(&mut self, _req: &Request, ino: u64, _fh: Option<u64>, reply: ReplyAttr) {
+        match self.attr(ino) {
+            Some(attr) => reply.attr(&TTL, &attr),
+            None => reply.error(libc::ENOENT),
+        }
+    }
+
+    fn readdir(
+        &mut self,
+        _req: &Request,
+        ino: u64,
+        _fh: u64,
+        offset: i64,
+        mut reply: ReplyDirectory,
+    ) {
+        let Some(Inode::Dir { children }) = self.inodes.get(ino as usize) else {
+            reply.error(libc::ENOTDIR);
+            return;
+        };
+
+        let mut entries = vec![
+            (ino, FileType::Directory, ".".to_string()),
+            (ino, FileType::Directory, "..".to_string()),
+        ];
+        for (name, child_ino) in children {
+            // Every inode's kind is already recorded in its attr, so reuse
+            // that instead of re-deriving it from the `Inode` variant.
+            let kind = self.attr(*child_ino).map(|a| a.kind).unwrap_or(FileType::RegularFile);
+            entries.push((*child_ino, kind, name.clone()));
+        }
+
+        for (i, (entry_ino, kind, name)) in entries.into_iter().enumerate().skip(offset as usize) {
+            if reply.add(entry_ino, (i + 1) as i64, kind, name) {
+                break;
+            }
+        }
+        reply.ok();
+    }
+
+    fn read(
+        &mut self,
+        _req: &Request,
+        ino: u64,
+        _fh: u64,
+        offset: i64,
+        size: u32,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        reply: ReplyData,
+    ) {
+        let Some(Inode::File { index }) = self.inodes.get(ino as usize) else {
+            reply.error(libc::EISDIR);
+            return;
+        };
+        let index = *index;
+
+        match self.cached_content(ino, index) {
+            Ok(content) => {
+                let start = (offset as usize).min(content.len());
+                let end = (start + size as usize).min(content.len());
+                reply.data(&content[start..end]);
+            }
+            Err(_) => reply.error(libc::EIO),
+        }
+    }
+
+    fn readlink(&mut self, _req: &Request, ino: u64, reply: ReplyData) {
+        let Some(Inode::Other { index }) = self.inodes.get(ino as usize) else {
+            reply.error(libc::EINVAL);
+            return;
+        };
+        let file = &self.archive.catalog.files[*index];
+
+        match (file.entry_type, file.link_target.as_deref()) {
+            (EntryType::Symlink, Some(target)) => reply.data(target.as_bytes()),
+            _ => reply.error(libc::EINVAL),
+        }
+    }
+}
+
+struct Args {
+    archive_path: String,
+    mountpoint: String,
+    password: Option<String>,
+}
+
+fn parse_args() -> Args {
+    let raw: Vec<String> = std::env::args().collect();
+    let mut positional = Vec::new();
+    let mut password = None;
+
+    let mut iter = raw.into_iter().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--password" => password = iter.next(),
+            other => positional.push(other.to_string()),
+        }
+    }
+
+    if positional.len() != 2 {
+        eprintln!("Usage: slorpfs <archive.pdf> <mountpoint> [--password PASSWORD]");
+        std::process::exit(1);
+    }
+
+    Args {
+        archive_path: positional[0].clone(),
+        mountpoint: positional[1].clone(),
+        password,
+    }
+}
+
+fn main() -> Result<()> {
+    let args = parse_args();
+    let archive_path = Path::new(&args.archive_path);
+
+    let archive = OpenArchive::open(archive_path, args.password.as_deref())
+        .with_context(|| format!("Failed to open archive {}", archive_path.display()))?;
+
+    println!(
+        "Mounting {} ({} files) at {}",
+        archive_path.display(),
+        archive.catalog.files.len(),
+        args.mountpoint
+    );
+
+    let fs = SlorpitFs::new(archive);
+    let options = vec![MountOption::RO, MountOption::FSName("slorpit".to_string())];
+    fuser::mount2(fs, &args.mountpoint, &options)
+        .map_err(|e| anyhow!("Failed to mount at {}: {}", args.mountpoint, e))
+}
@@ -0,0 +1,197 @@
+//! Compacts a slorpit archive into a fresh PDF, copying only the file
+//! streams and dictionary stream the catalog still references and
+//! regenerating the catalog stream and listing page. `lopdf` never reclaims objects that fall out of use, so
+//! editing or re-saving an archive can otherwise leave stale
+//! `EmbeddedFile` bytes sitting in the file - an information-leak and
+//! bloat hazard this defragments away.
+
+use anyhow::{Context, Result};
+use lopdf::{Document, Object, Stream, dictionary};
+use slorpit::archive::OpenArchive;
+use slorpit::crypto;
+use slorpit::{
+    CATALOG_KEY, DICTIONARY_KEY, ENCRYPTION_ALGORITHM_KEY, ENCRYPTION_KDF_KEY,
+    ENCRYPTION_SALT_KEY, STREAM_NONCE_KEY,
+};
+use std::fs;
+use std::path::Path;
+
+struct Args {
+    input_path: String,
+    output_path: String,
+    password: Option<String>,
+}
+
+fn parse_args() -> Args {
+    let raw: Vec<String> = std::env::args().collect();
+    let mut positional = Vec::new();
+    let mut password = None;
+
+    let mut iter = raw.into_iter().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--password" => password = iter.next(),
+            other => positional.push(other.to_string()),
+        }
+    }
+
+    if positional.len() != 2 {
+        eprintln!("Usage: slorprebuild <input.pdf> <output.pdf> [--password PASSWORD]");
+        std::process::exit(1);
+    }
+
+    Args {
+        input_path: positional[0].clone(),
+        output_path: positional[1].clone(),
+        password,
+    }
+}
+
+fn main() -> Result<()> {
+    let args = parse_args();
+
+    println!("Rebuilding {} -> {}", args.input_path, args.output_path);
+
+    let source = OpenArchive::open(Path::new(&args.input_path), args.password.as_deref())?;
+    let mut catalog = source.catalog.clone();
+
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+
+    for (idx, file_entry) in catalog.files.iter_mut().enumerate() {
+        let Some(old_id) = source.resolved_stream(idx) else {
+            continue;
+        };
+
+        let old_object = source
+            .doc
+            .get_object(old_id)
+            .with_context(|| format!("Missing stream object for {}", file_entry.path))?;
+        let old_stream = old_object
+            .as_stream()
+            .with_context(|| format!("Object for {} is not a stream", file_entry.path))?;
+
+        let new_stream = Stream::new(old_stream.dict.clone(), old_stream.content.clone());
+        file_entry.stream = Some(doc.add_object(new_stream));
+    }
+
+    if let Some(old_dict_id) = catalog.dictionary {
+        let old_object = source
+            .doc
+            .get_object(old_dict_id)
+            .context("Missing referenced dictionary stream")?;
+        let old_stream = old_object
+            .as_stream()
+            .context("Dictionary object is not a stream")?;
+        let new_stream = Stream::new(old_stream.dict.clone(), old_stream.content.clone());
+        catalog.dictionary = Some(doc.add_object(new_stream));
+    }
+
+    let catalog_json = serde_json::to_string(&catalog)?;
+
+    // The copied file/dictionary streams above keep their original ciphertext
+    // and per-stream nonce untouched, so if the source was encrypted, the
+    // rebuilt catalog must stay encrypted too, under the same key - otherwise
+    // OpenArchive::open would see an unencrypted catalog and try to treat
+    // those streams' bytes as plain compressed data instead of decrypting them.
+    let (catalog_bytes, catalog_nonce) = match (&catalog.encryption, source.decrypt_key()) {
+        (Some(_), Some(key)) => {
+            let nonce = crypto::random_nonce();
+            let ciphertext = crypto::encrypt(key, &nonce, catalog_json.as_bytes())?;
+            (ciphertext, Some(nonce))
+        }
+        (Some(_), None) => {
+            anyhow::bail!("Source archive is encrypted; --password is required to rebuild it")
+        }
+        (None, _) => (catalog_json.into_bytes(), None),
+    };
+
+    let mut catalog_stream_dict = dictionary! {
+        "Type" => "Metadata",
+        "Subtype" => "SlorpitArchive",
+    };
+    if let Some(meta) = &catalog.encryption {
+        catalog_stream_dict.set(
+            ENCRYPTION_ALGORITHM_KEY,
+            Object::Name(meta.algorithm.as_bytes().to_vec()),
+        );
+        catalog_stream_dict.set(
+            ENCRYPTION_KDF_KEY,
+            Object::Name(meta.kdf.as_bytes().to_vec()),
+        );
+        catalog_stream_dict.set(
+            ENCRYPTION_SALT_KEY,
+            Object::String(meta.salt.clone(), lopdf::StringFormat::Literal),
+        );
+    }
+    if let Some(nonce) = &catalog_nonce {
+        catalog_stream_dict.set(
+            STREAM_NONCE_KEY,
+            Object::String(nonce.to_vec(), lopdf::StringFormat::Literal),
+        );
+    }
+
+    let catalog_stream = Stream::new(catalog_stream_dict, catalog_bytes);
+    let catalog_id = doc.add_object(catalog_stream);
+
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Courier",
+    });
+
+    let page_content = slorpit::page::create_file_listing_content(&catalog)?;
+    let content_stream = Stream::new(dictionary! {}, page_content.as_bytes().to_vec());
+    let content_id = doc.add_object(content_stream);
+
+    let page_id = doc.add_object(dictionary! {
+        "Type" => "Page",
+        "Parent" => pages_id,
+        "Contents" => content_id,
+        "Resources" => dictionary! {
+            "Font" => dictionary! {
+                "F1" => font_id,
+            },
+        },
+        "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+    });
+
+    let pages = dictionary! {
+        "Type" => "Pages",
+        "Kids" => vec![page_id.into()],
+        "Count" => 1,
+    };
+    doc.objects.insert(pages_id, Object::Dictionary(pages));
+
+    let mut catalog_dict = dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+        CATALOG_KEY => catalog_id,
+    };
+    if let Some(dict_id) = catalog.dictionary {
+        catalog_dict.set(DICTIONARY_KEY, Object::Reference(dict_id));
+    }
+    let catalog_obj_id = doc.add_object(catalog_dict);
+    doc.trailer.set("Root", catalog_obj_id);
+
+    doc.compress();
+
+    let save_options = lopdf::SaveOptions::builder()
+        .compression_level(9)
+        .use_object_streams(true)
+        .max_objects_per_stream(200)
+        .build();
+
+    let mut file = fs::File::create(&args.output_path)
+        .with_context(|| format!("Failed to create output file {}", args.output_path))?;
+    doc.save_with_options(&mut file, save_options)
+        .with_context(|| format!("Failed to save PDF to {}", args.output_path))?;
+
+    println!(
+        "Rebuilt archive with {} files into {}",
+        catalog.files.len(),
+        args.output_path
+    );
+
+    Ok(())
+}
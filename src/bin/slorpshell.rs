@@ -0,0 +1,234 @@
+//! Interactive REPL for browsing a slorpit archive's reconstructed
+//! directory tree and selectively restoring files out of it.
+
+use anyhow::{Context, Result};
+use slorpit::EntryType;
+use slorpit::archive::OpenArchive;
+use slorpit::filter::PathFilter;
+use slorpit::tree::{Node, Tree};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+struct Args {
+    archive_path: String,
+    password: Option<String>,
+}
+
+fn parse_args() -> Args {
+    let raw: Vec<String> = std::env::args().collect();
+    let mut positional = Vec::new();
+    let mut password = None;
+
+    let mut iter = raw.into_iter().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--password" => password = iter.next(),
+            other => positional.push(other.to_string()),
+        }
+    }
+
+    if positional.len() != 1 {
+        eprintln!("Usage: slorpshell <archive.pdf> [--password PASSWORD]");
+        std::process::exit(1);
+    }
+
+    Args {
+        archive_path: positional[0].clone(),
+        password,
+    }
+}
+
+fn main() -> Result<()> {
+    let args = parse_args();
+
+    let archive = OpenArchive::open(Path::new(&args.archive_path), args.password.as_deref())?;
+    let tree = Tree::build(&archive.catalog.files);
+
+    println!(
+        "Opened {} ({} files). Type 'help' for commands.",
+        args.archive_path,
+        archive.catalog.files.len()
+    );
+
+    let mut cwd: Vec<String> = Vec::new();
+    let stdin = io::stdin();
+
+    loop {
+        print!("slorpit:/{}> ", cwd.join("/"));
+        io::stdout().flush()?;
+
+        let mut line = String::new();
+        if stdin.read_line(&mut line)? == 0 {
+            break;
+        }
+
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else {
+            continue;
+        };
+        let rest: Vec<&str> = words.collect();
+
+        if let Err(e) = run_command(&archive, &tree, &mut cwd, command, &rest) {
+            match command {
+                "exit" | "quit" => break,
+                _ => eprintln!("error: {}", e),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn run_command(
+    archive: &OpenArchive,
+    tree: &Tree,
+    cwd: &mut Vec<String>,
+    command: &str,
+    args: &[&str],
+) -> Result<()> {
+    match command {
+        "help" => {
+            println!(
+                "Commands: ls [path], cd <path>, stat <path>, cat <path>, restore <glob> [target_dir] [--exclude GLOB]..., pwd, exit"
+            );
+        }
+        "pwd" => println!("/{}", cwd.join("/")),
+        "ls" => {
+            let parts = resolve(cwd, args.first().copied().unwrap_or("."));
+            match lookup(tree, &parts)? {
+                Node::Dir { children, .. } => {
+                    for name in children.keys() {
+                        println!("{}", name);
+                    }
+                }
+                Node::File(_) | Node::Other(_) => {
+                    println!("{}", parts.last().cloned().unwrap_or_default())
+                }
+            }
+        }
+        "cd" => {
+            let parts = resolve(cwd, args.first().copied().unwrap_or("/"));
+            match lookup(tree, &parts)? {
+                Node::Dir { .. } => *cwd = parts,
+                Node::File(_) | Node::Other(_) => anyhow::bail!("not a directory"),
+            }
+        }
+        "stat" => {
+            let target = args.first().context("usage: stat <path>")?;
+            let parts = resolve(cwd, target);
+            match lookup(tree, &parts)? {
+                Node::Dir { children, .. } => println!("directory, {} entries", children.len()),
+                Node::File(idx) => {
+                    let entry = &archive.catalog.files[*idx];
+                    println!("file, {} bytes, modified={:?}", entry.size, entry.modified);
+                }
+                Node::Other(idx) => {
+                    let entry = &archive.catalog.files[*idx];
+                    println!(
+                        "{:?}, target={:?}",
+                        entry.entry_type, entry.link_target
+                    );
+                }
+            }
+        }
+        "cat" => {
+            let target = args.first().context("usage: cat <path>")?;
+            let parts = resolve(cwd, target);
+            match lookup(tree, &parts)? {
+                Node::File(idx) => {
+                    let content = archive.read_file(*idx)?;
+                    io::stdout().write_all(&content)?;
+                }
+                Node::Dir { .. } => anyhow::bail!("is a directory"),
+                Node::Other(idx) => {
+                    let entry = &archive.catalog.files[*idx];
+                    anyhow::bail!("not a regular file ({:?})", entry.entry_type);
+                }
+            }
+        }
+        "restore" => {
+            let pattern = args
+                .first()
+                .context("usage: restore <glob> [target_dir] [--exclude GLOB]...")?;
+
+            let mut target_dir = ".";
+            let mut exclude = Vec::new();
+            let mut rest = args[1..].iter();
+            while let Some(&arg) = rest.next() {
+                if arg == "--exclude" {
+                    let pattern = rest
+                        .next()
+                        .context("--exclude requires a glob argument")?;
+                    exclude.push(pattern.to_string());
+                } else {
+                    target_dir = arg;
+                }
+            }
+
+            restore(archive, pattern, target_dir, &exclude)?;
+        }
+        other => anyhow::bail!("unknown command: {}", other),
+    }
+
+    Ok(())
+}
+
+fn resolve(cwd: &[String], input: &str) -> Vec<String> {
+    let mut parts = if input.starts_with('/') {
+        Vec::new()
+    } else {
+        cwd.to_vec()
+    };
+
+    for component in input.split('/').filter(|c| !c.is_empty()) {
+        match component {
+            "." => {}
+            ".." => {
+                parts.pop();
+            }
+            other => parts.push(other.to_string()),
+        }
+    }
+
+    parts
+}
+
+fn lookup<'a>(tree: &'a Tree, parts: &[String]) -> Result<&'a Node> {
+    tree.lookup(&parts.join("/"))
+        .with_context(|| format!("no such path: /{}", parts.join("/")))
+}
+
+fn restore(archive: &OpenArchive, pattern: &str, target_dir: &str, exclude: &[String]) -> Result<()> {
+    let filter = PathFilter::new(std::slice::from_ref(&pattern.to_string()), exclude)?;
+    let output_path = Path::new(target_dir);
+    let mut restored = 0;
+
+    for (idx, file_entry) in archive.catalog.files.iter().enumerate() {
+        if !filter.matches(&file_entry.path) {
+            continue;
+        }
+        if file_entry.entry_type != EntryType::File {
+            // Directories, symlinks, and the rest have no stream to
+            // restore; `unslorp` handles reconstructing them, this REPL
+            // command only pulls out plain file bytes.
+            continue;
+        }
+
+        let content = archive.read_file(idx)?;
+        let file_path = output_path.join(&file_entry.path);
+
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&file_path, &content)
+            .with_context(|| format!("Failed to write {}", file_path.display()))?;
+
+        println!("  restored: {}", file_entry.path);
+        restored += 1;
+    }
+
+    println!("Restored {} files to {}", restored, target_dir);
+
+    Ok(())
+}
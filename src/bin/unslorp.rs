@@ -1,147 +1,185 @@
-use anyhow::{Context, Result, anyhow};
-use lopdf::{Document, ObjectId};
-use slorpit::{ArchiveCatalog, CATALOG_KEY};
+use anyhow::{Context, Result};
+use slorpit::archive::OpenArchive;
+use slorpit::filter::PathFilter;
+use slorpit::{EntryType, FileEntry};
 use std::fs;
 use std::io::Write;
+use std::os::unix::fs::PermissionsExt;
 use std::path::Path;
 
-fn main() -> Result<()> {
-    let args: Vec<String> = std::env::args().collect();
+struct Args {
+    input_path: String,
+    output_dir: String,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    password: Option<String>,
+}
 
-    if args.len() < 2 {
-        eprintln!("Usage: {} <archive.pdf> [output_directory]", args[0]);
-        std::process::exit(1);
+fn parse_args() -> Args {
+    let raw: Vec<String> = std::env::args().collect();
+    let mut positional = Vec::new();
+    let mut include = Vec::new();
+    let mut exclude = Vec::new();
+    let mut password = None;
+
+    let mut iter = raw.into_iter().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--pattern" => {
+                if let Some(pattern) = iter.next() {
+                    include.push(pattern);
+                }
+            }
+            "--exclude" => {
+                if let Some(pattern) = iter.next() {
+                    exclude.push(pattern);
+                }
+            }
+            "--password" => {
+                password = iter.next();
+            }
+            other => positional.push(other.to_string()),
+        }
     }
 
-    let input_path = &args[1];
-    let output_dir = if args.len() >= 3 { &args[2] } else { "." };
+    if positional.is_empty() {
+        eprintln!(
+            "Usage: unslorp <archive.pdf> [output_directory] [--pattern GLOB] [--exclude GLOB] [--password PASSWORD]"
+        );
+        std::process::exit(1);
+    }
 
-    println!("Extracting PDF archive: {}", input_path);
+    Args {
+        input_path: positional[0].clone(),
+        output_dir: positional.get(1).cloned().unwrap_or_else(|| ".".to_string()),
+        include,
+        exclude,
+        password,
+    }
+}
 
-    let doc = Document::load(input_path)
-        .with_context(|| format!("Failed to load PDF from {}", input_path))?;
+fn main() -> Result<()> {
+    let args = parse_args();
 
-    let catalog_id = find_catalog_id(&doc)?;
+    println!("Extracting PDF archive: {}", args.input_path);
 
-    let catalog = extract_catalog(&doc, catalog_id)?;
+    let archive = OpenArchive::open(Path::new(&args.input_path), args.password.as_deref())?;
+    let filter = PathFilter::new(&args.include, &args.exclude)?;
 
-    println!("Found {} files in archive", catalog.files.len());
+    println!("Found {} files in archive", archive.catalog.files.len());
 
-    let output_path = Path::new(output_dir);
+    let output_path = Path::new(&args.output_dir);
     fs::create_dir_all(output_path)?;
 
-    let file_streams = find_file_streams(&doc)?;
-
-    for (idx, file_entry) in catalog.files.iter().enumerate() {
-        println!("  Extracting: {}", file_entry.path);
+    let mut extracted = 0;
+    let mut restored_dirs = Vec::new();
 
-        if idx >= file_streams.len() {
-            eprintln!("Warning: Missing stream for {}", file_entry.path);
+    for (idx, file_entry) in archive.catalog.files.iter().enumerate() {
+        if !filter.matches(&file_entry.path) {
             continue;
         }
 
-        let stream_id = file_streams[idx];
-        let content = extract_file_content(&doc, stream_id)?;
+        println!("  Extracting: {}", file_entry.path);
 
         let file_path = output_path.join(&file_entry.path);
 
-        if let Some(parent) = file_path.parent() {
-            fs::create_dir_all(parent)?;
+        if let Err(e) = extract_entry(&archive, idx, file_entry, &file_path, output_path) {
+            eprintln!("Warning: Skipping {}: {}", file_entry.path, e);
+            continue;
         }
 
-        let mut file = fs::File::create(&file_path)
-            .with_context(|| format!("Failed to create {}", file_path.display()))?;
-        file.write_all(&content)?;
+        if file_entry.entry_type == EntryType::Directory {
+            restored_dirs.push((file_path, file_entry.modified));
+        }
 
-        if let Some(modified) = file_entry.modified {
+        extracted += 1;
+    }
+
+    // catalog.files lists a directory before the entries it contains
+    // (WalkDir pre-order), so extracting anything inside one bumps its
+    // mtime again. Restore directory mtimes in a second pass, deepest
+    // first, after every entry has been written.
+    restored_dirs.reverse();
+    for (dir_path, modified) in restored_dirs {
+        if let Some(modified) = modified {
             let time = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(modified);
-            let _ =
-                filetime::set_file_mtime(&file_path, filetime::FileTime::from_system_time(time));
+            let _ = filetime::set_file_mtime(&dir_path, filetime::FileTime::from_system_time(time));
         }
     }
 
     println!(
         "Successfully extracted {} files to {}",
-        catalog.files.len(),
-        output_dir
+        extracted, args.output_dir
     );
 
     Ok(())
 }
 
-fn find_catalog_id(doc: &Document) -> Result<ObjectId> {
-    let trailer = &doc.trailer;
-    let root_obj = trailer.get(b"Root").context("No Root in PDF trailer")?;
-    let root_id = root_obj.as_reference().context("Root is not a reference")?;
-
-    let root = doc
-        .get_object(root_id)
-        .context("Failed to get root object")?
-        .as_dict()
-        .context("Invalid Root catalog")?;
-
-    let catalog_obj = root
-        .get(CATALOG_KEY.as_bytes())
-        .context("No Slorpit catalog found in PDF")?;
-    let catalog_id = catalog_obj
-        .as_reference()
-        .context("Catalog is not a reference")?;
-
-    Ok(catalog_id)
-}
-
-fn extract_catalog(doc: &Document, catalog_id: ObjectId) -> Result<ArchiveCatalog> {
-    let catalog_obj = doc
-        .get_object(catalog_id)
-        .map_err(|e| anyhow!("Catalog object not found: {}", e))?;
-
-    let stream = catalog_obj
-        .as_stream()
-        .map_err(|e| anyhow!("Catalog is not a stream: {}", e))?;
-
-    let content = if stream.dict.get(b"Filter").is_ok() {
-        stream.decompressed_content()?
-    } else {
-        stream.content.clone()
-    };
-    let catalog: ArchiveCatalog =
-        serde_json::from_slice(&content).with_context(|| "Failed to parse catalog JSON")?;
-
-    Ok(catalog)
-}
-
-fn find_file_streams(doc: &Document) -> Result<Vec<ObjectId>> {
-    let mut file_streams = Vec::new();
+fn extract_entry(
+    archive: &OpenArchive,
+    idx: usize,
+    file_entry: &FileEntry,
+    file_path: &Path,
+    output_path: &Path,
+) -> Result<()> {
+    if let Some(parent) = file_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
 
-    for (object_id, object) in &doc.objects {
-        if let Ok(stream) = object.as_stream() {
-            if let Ok(dict) = stream.dict.get(b"Type") {
-                if let Ok(type_name) = dict.as_name() {
-                    if type_name == b"EmbeddedFile" {
-                        file_streams.push(*object_id);
-                    }
-                }
+    match file_entry.entry_type {
+        EntryType::Directory => {
+            fs::create_dir_all(file_path)?;
+        }
+        EntryType::Symlink => {
+            let target = file_entry
+                .link_target
+                .as_deref()
+                .context("Symlink entry has no link_target")?;
+            if file_path.symlink_metadata().is_err() {
+                std::os::unix::fs::symlink(target, file_path)
+                    .with_context(|| format!("Failed to create symlink {}", file_path.display()))?;
             }
+            return Ok(());
+        }
+        EntryType::Hardlink => {
+            let original = file_entry
+                .link_target
+                .as_deref()
+                .context("Hardlink entry has no link_target")?;
+            let original_path = output_path.join(original);
+            fs::hard_link(&original_path, file_path).with_context(|| {
+                format!(
+                    "Failed to hard link {} to {}",
+                    file_path.display(),
+                    original_path.display()
+                )
+            })?;
+            return Ok(());
+        }
+        EntryType::File => {
+            let content = archive.read_file(idx)?;
+            let mut file = fs::File::create(file_path)
+                .with_context(|| format!("Failed to create {}", file_path.display()))?;
+            file.write_all(&content)?;
+        }
+        other => {
+            anyhow::bail!("Unsupported entry type {:?}, skipping", other);
         }
     }
 
-    file_streams.sort_by_key(|(id, generation)| (*id, *generation));
-
-    Ok(file_streams)
-}
-
-fn extract_file_content(doc: &Document, object_id: ObjectId) -> Result<Vec<u8>> {
-    let object = doc
-        .get_object(object_id)
-        .map_err(|e| anyhow!("Object not found: {:?}: {}", object_id, e))?;
-
-    let stream = object
-        .as_stream()
-        .map_err(|e| anyhow!("Object is not a stream: {:?}: {}", object_id, e))?;
+    if let Some(mode) = file_entry.mode {
+        let _ = fs::set_permissions(file_path, fs::Permissions::from_mode(mode));
+    }
 
-    let content = stream
-        .decompressed_content()
-        .with_context(|| format!("Failed to decompress stream: {:?}", object_id))?;
+    // Directory mtimes are restored separately once every entry has been
+    // written, since creating entries inside a directory bumps its mtime
+    // right back (see the second pass in `main`).
+    if file_entry.entry_type != EntryType::Directory {
+        if let Some(modified) = file_entry.modified {
+            let time = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(modified);
+            let _ = filetime::set_file_mtime(file_path, filetime::FileTime::from_system_time(time));
+        }
+    }
 
-    Ok(content)
+    Ok(())
 }
@@ -0,0 +1,88 @@
+//! Shared compression helpers used by both the creator and the extractor.
+//!
+//! Archives are compressed either per-file with plain zlib/flate, or, when
+//! there are enough similar small files to make it worthwhile, with a single
+//! zstd dictionary trained across all inputs.
+
+use anyhow::{Context, Result};
+
+/// Minimum number of file samples before we bother training a dictionary.
+pub const MIN_SAMPLES: usize = 8;
+
+/// Minimum combined sample size before we bother training a dictionary.
+pub const MIN_TOTAL_SAMPLE_BYTES: usize = 4 * 1024;
+
+/// Target size for a trained dictionary.
+pub const DICT_TARGET_SIZE: usize = 96 * 1024;
+
+/// Train a shared zstd dictionary from sampled file contents.
+///
+/// Returns `None` when there are too few or too small samples for the
+/// dictionary to be worth the overhead, in which case callers should fall
+/// back to plain flate compression.
+pub fn train_dictionary(samples: &[Vec<u8>]) -> Option<Vec<u8>> {
+    let total: usize = samples.iter().map(|s| s.len()).sum();
+    if samples.len() < MIN_SAMPLES || total < MIN_TOTAL_SAMPLE_BYTES {
+        return None;
+    }
+
+    zstd::dict::from_samples(samples, DICT_TARGET_SIZE).ok()
+}
+
+/// Compress `data` against a prepared zstd dictionary.
+pub fn compress_with_dictionary(
+    data: &[u8],
+    dictionary: &zstd::dict::EncoderDictionary,
+) -> Result<Vec<u8>> {
+    let mut compressor = zstd::bulk::Compressor::with_prepared_dictionary(dictionary)
+        .context("Failed to initialize zstd compressor")?;
+    compressor
+        .compress(data)
+        .context("zstd dictionary compression failed")
+}
+
+/// Decompress `data` against a prepared zstd dictionary.
+///
+/// `capacity_hint` is the upper bound on the decompressed size: zstd's bulk
+/// decompressor allocates that much and fails if the output would be larger,
+/// so pass the original (uncompressed) size.
+pub fn decompress_with_dictionary(
+    data: &[u8],
+    dictionary: &zstd::dict::DecoderDictionary,
+    capacity_hint: usize,
+) -> Result<Vec<u8>> {
+    let mut decompressor = zstd::bulk::Decompressor::with_prepared_dictionary(dictionary)
+        .context("Failed to initialize zstd decompressor")?;
+    decompressor
+        .decompress(data, capacity_hint)
+        .context("zstd dictionary decompression failed")
+}
+
+/// Compress `data` with plain zlib, the legacy per-file scheme.
+pub fn compress_flate(data: &[u8]) -> Result<Vec<u8>> {
+    use flate2::Compression;
+    use flate2::write::ZlibEncoder;
+    use std::io::Write;
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+/// Decompress plain zlib data manually.
+///
+/// Normally lopdf's `Stream::decompressed_content()` handles this via the
+/// stream's `Filter` entry, but encrypted streams have ciphertext in place
+/// of the stream content until decrypted, so the `Filter`-based path can't
+/// run until after that decryption happens.
+pub fn decompress_flate(data: &[u8]) -> Result<Vec<u8>> {
+    use flate2::read::ZlibDecoder;
+    use std::io::Read;
+
+    let mut decoder = ZlibDecoder::new(data);
+    let mut out = Vec::new();
+    decoder
+        .read_to_end(&mut out)
+        .context("flate decompression failed")?;
+    Ok(out)
+}
@@ -0,0 +1,52 @@
+//! Password-based encryption for the catalog and embedded file streams.
+//!
+//! Keys are derived with Argon2id from a per-archive random salt; each
+//! encrypted object (the catalog, and every `EmbeddedFile` stream) gets its
+//! own random nonce under ChaCha20-Poly1305, keeping the key reusable
+//! across the whole archive.
+
+use anyhow::{Result, anyhow};
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::RngCore;
+
+pub const ALGORITHM: &str = "chacha20poly1305";
+pub const KDF: &str = "argon2id";
+pub const SALT_LEN: usize = 16;
+pub const NONCE_LEN: usize = 12;
+pub const KEY_LEN: usize = 32;
+
+pub fn random_salt() -> [u8; SALT_LEN] {
+    let mut salt = [0u8; SALT_LEN];
+    rand::rngs::OsRng.fill_bytes(&mut salt);
+    salt
+}
+
+pub fn random_nonce() -> [u8; NONCE_LEN] {
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+    nonce
+}
+
+pub fn derive_key(password: &str, salt: &[u8]) -> Result<[u8; KEY_LEN]> {
+    let mut key = [0u8; KEY_LEN];
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+pub fn encrypt(key: &[u8; KEY_LEN], nonce: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    cipher
+        .encrypt(Nonce::from_slice(nonce), plaintext)
+        .map_err(|e| anyhow!("Encryption failed: {}", e))
+}
+
+pub fn decrypt(key: &[u8; KEY_LEN], nonce: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>> {
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|e| anyhow!("Decryption failed, wrong password?: {}", e))
+}
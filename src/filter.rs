@@ -0,0 +1,37 @@
+//! Include/exclude glob filtering shared between the extractor's
+//! `--pattern`/`--exclude` flags and the interactive shell's `restore`
+//! command.
+
+use anyhow::{Context, Result};
+use glob::Pattern;
+
+pub struct PathFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl PathFilter {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self> {
+        Ok(Self {
+            include: compile(include)?,
+            exclude: compile(exclude)?,
+        })
+    }
+
+    /// True if `path` should be selected: not excluded, and matching at
+    /// least one include pattern (or there are no include patterns, in
+    /// which case everything not excluded matches).
+    pub fn matches(&self, path: &str) -> bool {
+        if self.exclude.iter().any(|p| p.matches(path)) {
+            return false;
+        }
+        self.include.is_empty() || self.include.iter().any(|p| p.matches(path))
+    }
+}
+
+fn compile(patterns: &[String]) -> Result<Vec<Pattern>> {
+    patterns
+        .iter()
+        .map(|p| Pattern::new(p).with_context(|| format!("Invalid glob pattern: {}", p)))
+        .collect()
+}
@@ -1,16 +1,104 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+pub mod archive;
+pub mod compress;
+pub mod crypto;
+pub mod filter;
+pub mod page;
+pub mod tree;
+
+/// What kind of filesystem entry a `FileEntry` represents.
+///
+/// Defaults to `File` on deserialization so archives written before this
+/// field existed (which only ever embedded regular files) still load.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EntryType {
+    File,
+    Directory,
+    Symlink,
+    Hardlink,
+    Fifo,
+    Socket,
+    CharDevice,
+    BlockDevice,
+}
+
+impl Default for EntryType {
+    fn default() -> Self {
+        EntryType::File
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileEntry {
     pub path: String,
     pub size: u64,
     pub modified: Option<u64>,
+    /// Object reference of this file's `EmbeddedFile` stream.
+    ///
+    /// Only set for `EntryType::File`. Absent on archives written before
+    /// this field existed; extraction falls back to sorted-order matching
+    /// for those.
+    #[serde(default)]
+    pub stream: Option<(u32, u16)>,
+    #[serde(default)]
+    pub entry_type: EntryType,
+    /// Unix permission bits, e.g. from `st_mode & 0o7777`.
+    #[serde(default)]
+    pub mode: Option<u32>,
+    #[serde(default)]
+    pub uid: Option<u32>,
+    #[serde(default)]
+    pub gid: Option<u32>,
+    /// Symlink target for `EntryType::Symlink`, or the already-archived
+    /// path this entry is a hard link to for `EntryType::Hardlink`.
+    #[serde(default)]
+    pub link_target: Option<String>,
+}
+
+/// Which scheme was used to compress the embedded file streams.
+///
+/// Stored on `ArchiveCatalog` so that older archives (which predate
+/// dictionary support) keep extracting correctly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CompressionAlgorithm {
+    Flate,
+    ZstdDict,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl Default for CompressionAlgorithm {
+    fn default() -> Self {
+        CompressionAlgorithm::Flate
+    }
+}
+
+/// Records how an encrypted archive's key can be reconstructed from a
+/// password. The salt is not secret; it only needs to survive alongside
+/// the ciphertext so the same key can be re-derived.
+///
+/// This is mirrored into the PDF catalog stream's (unencrypted) dict so
+/// extraction can read it before the catalog JSON itself is decrypted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptionMetadata {
+    pub algorithm: String,
+    pub kdf: String,
+    pub salt: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ArchiveCatalog {
     pub files: Vec<FileEntry>,
     pub version: String,
+    #[serde(default)]
+    pub compression: CompressionAlgorithm,
+    /// Object reference of the shared zstd dictionary stream, when
+    /// `compression` is `ZstdDict`.
+    #[serde(default)]
+    pub dictionary: Option<(u32, u16)>,
+    #[serde(default)]
+    pub encryption: Option<EncryptionMetadata>,
 }
 
 impl ArchiveCatalog {
@@ -18,8 +106,33 @@ impl ArchiveCatalog {
         Self {
             files: Vec::new(),
             version: "1.0".to_string(),
+            compression: CompressionAlgorithm::default(),
+            dictionary: None,
+            encryption: None,
         }
     }
 }
 
 pub const CATALOG_KEY: &str = "SlorpitCatalog";
+
+/// Root-catalog entry pointing at the shared zstd dictionary stream, stored
+/// alongside `CATALOG_KEY` when the archive uses dictionary compression.
+pub const DICTIONARY_KEY: &str = "SlorpitDictionary";
+
+/// Per-stream dict entry recording which scheme compressed that stream
+/// (`"flate"` or `"zstd-dict"`), so extraction doesn't have to guess.
+pub const STREAM_COMPRESSION_KEY: &str = "SlorpitCompression";
+
+/// Per-stream dict entry holding that object's AEAD nonce, present on any
+/// stream (catalog, dictionary, or embedded file) encrypted with `--encrypt`.
+pub const STREAM_NONCE_KEY: &str = "SlorpitNonce";
+
+/// Catalog-stream dict entry naming the AEAD cipher, e.g. `"chacha20poly1305"`.
+pub const ENCRYPTION_ALGORITHM_KEY: &str = "SlorpitEncryption";
+
+/// Catalog-stream dict entry naming the key-derivation function, e.g. `"argon2id"`.
+pub const ENCRYPTION_KDF_KEY: &str = "SlorpitKdf";
+
+/// Catalog-stream dict entry holding the per-archive salt used to derive
+/// the encryption key from the user's password.
+pub const ENCRYPTION_SALT_KEY: &str = "SlorpitSalt";
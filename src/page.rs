@@ -0,0 +1,83 @@
+//! Renders the human-readable file-listing page embedded alongside the
+//! archive data, so a slorpit PDF looks like a normal document in any PDF
+//! viewer. Shared between the creator and the `rebuild` subcommand, which
+//! both need to regenerate this page from an `ArchiveCatalog`.
+
+use crate::ArchiveCatalog;
+use anyhow::Result;
+
+pub fn create_file_listing_content(catalog: &ArchiveCatalog) -> Result<String> {
+    let mut content = String::new();
+
+    content.push_str("BT\n");
+    content.push_str("/F1 12 Tf\n");
+    content.push_str("50 750 Td\n");
+    content.push_str("(SLORPIT PDF Archive) Tj\n");
+    content.push_str("0 -20 Td\n");
+    content.push_str("/F1 10 Tf\n");
+
+    let header = format!("(Archive contains {} files)", catalog.files.len());
+    content.push_str(&header);
+    content.push_str(" Tj\n");
+    content.push_str("0 -25 Td\n");
+
+    content.push_str("/F1 9 Tf\n");
+    content.push_str("(Filename) Tj\n");
+    content.push_str("300 0 Td\n");
+    content.push_str("(Size) Tj\n");
+    content.push_str("100 0 Td\n");
+    content.push_str("(Modified) Tj\n");
+    content.push_str("-400 -15 Td\n");
+
+    for file in &catalog.files {
+        let filename = escape_pdf_string(&file.path);
+        content.push_str(&format!("({}) Tj\n", filename));
+        content.push_str("300 0 Td\n");
+
+        let size_str = format_size(file.size);
+        content.push_str(&format!("({}) Tj\n", size_str));
+        content.push_str("100 0 Td\n");
+
+        let modified_str = if let Some(ts) = file.modified {
+            format_timestamp(ts)
+        } else {
+            "N/A".to_string()
+        };
+        content.push_str(&format!("({}) Tj\n", modified_str));
+
+        content.push_str("-400 -12 Td\n");
+    }
+
+    content.push_str("ET\n");
+
+    Ok(content)
+}
+
+fn escape_pdf_string(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('(', "\\(")
+        .replace(')', "\\)")
+        .chars()
+        .filter(|c| c.is_ascii() && !c.is_control())
+        .collect()
+}
+
+fn format_size(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+fn format_timestamp(ts: u64) -> String {
+    use chrono::DateTime;
+
+    if let Some(datetime) = DateTime::from_timestamp(ts as i64, 0) {
+        datetime.format("%Y-%m-%d %H:%M").to_string()
+    } else {
+        "N/A".to_string()
+    }
+}
@@ -0,0 +1,91 @@
+//! Reconstructs a directory hierarchy from the flat `FileEntry.path` values
+//! recorded in an `ArchiveCatalog`, for tools that browse an archive like a
+//! filesystem (the FUSE mount, the interactive shell) without doing a full
+//! extraction pass.
+
+use crate::{EntryType, FileEntry};
+use std::collections::BTreeMap;
+
+/// A node in the reconstructed tree. `File` holds the index of the
+/// corresponding entry in `ArchiveCatalog::files` for an `EntryType::File`
+/// entry (the only kind with an actual `EmbeddedFile` stream to read);
+/// `Other` holds the index for a symlink/hardlink/device/fifo/socket entry,
+/// which has catalog metadata but no stream. A `Dir`'s `index` is `Some`
+/// when the directory has its own catalog entry (carrying mode/owner
+/// metadata) rather than being only implied by a descendant's path.
+#[derive(Debug)]
+pub enum Node {
+    Dir {
+        children: BTreeMap<String, Node>,
+        index: Option<usize>,
+    },
+    File(usize),
+    Other(usize),
+}
+
+pub struct Tree {
+    pub root: Node,
+}
+
+impl Tree {
+    pub fn build(files: &[FileEntry]) -> Self {
+        let mut root = BTreeMap::new();
+        for (idx, file) in files.iter().enumerate() {
+            let parts: Vec<&str> = file.path.split('/').filter(|p| !p.is_empty()).collect();
+            insert(&mut root, &parts, idx, file.entry_type);
+        }
+        Tree {
+            root: Node::Dir {
+                children: root,
+                index: None,
+            },
+        }
+    }
+
+    /// Resolve a `/`-separated path (relative to the archive root) to a node.
+    pub fn lookup(&self, path: &str) -> Option<&Node> {
+        let mut node = &self.root;
+        for part in path.split('/').filter(|p| !p.is_empty()) {
+            match node {
+                Node::Dir { children, .. } => node = children.get(part)?,
+                Node::File(_) | Node::Other(_) => return None,
+            }
+        }
+        Some(node)
+    }
+}
+
+fn insert(dir: &mut BTreeMap<String, Node>, parts: &[&str], idx: usize, entry_type: EntryType) {
+    match parts {
+        [] => {}
+        [name] if entry_type == EntryType::Directory => {
+            match dir.get_mut(*name) {
+                Some(Node::Dir { index, .. }) => *index = Some(idx),
+                _ => {
+                    dir.insert(
+                        (*name).to_string(),
+                        Node::Dir {
+                            children: BTreeMap::new(),
+                            index: Some(idx),
+                        },
+                    );
+                }
+            }
+        }
+        [name] if entry_type == EntryType::File => {
+            dir.insert((*name).to_string(), Node::File(idx));
+        }
+        [name] => {
+            dir.insert((*name).to_string(), Node::Other(idx));
+        }
+        [first, rest @ ..] => {
+            let entry = dir.entry((*first).to_string()).or_insert_with(|| Node::Dir {
+                children: BTreeMap::new(),
+                index: None,
+            });
+            if let Node::Dir { children, .. } = entry {
+                insert(children, rest, idx, entry_type);
+            }
+        }
+    }
+}